@@ -1,19 +1,43 @@
 use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool as R2d2Pool, PooledConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use dotenvy::dotenv;
 use std::{env, error::Error};
 
+/// Pool de conexiones SQLite compartido a través del estado de la app de Actix.
+pub type Pool = R2d2Pool<ConnectionManager<SqliteConnection>>;
+
+/// Conexión tomada prestada del `Pool`. Los casos de uso reciben este tipo
+/// en lugar de abrir una conexión nueva en cada llamada.
+pub type Connection = PooledConnection<ConnectionManager<SqliteConnection>>;
+
+/// Migraciones de Diesel embebidas en el binario, de modo que el servidor
+/// sea autocontenido y no dependa de que alguien corra `diesel migration run`.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
 /// Entidad encargada de generar operaciones básicas
 /// de base de datos desde diesel
 pub struct DieselConnector;
 impl DieselConnector {
-    /// Esta función se encarga de brindar una conexión
-    /// a la base de datos siempre que se haya definido
-    /// un `DATABASE_URL` en un archivo .env en la raiz
-    /// del proyecto
-    pub fn establish_connection() -> Result<SqliteConnection, Box<dyn Error>> {
+    /// Construye el `Pool` de conexiones a partir del `DATABASE_URL`
+    /// definido en un archivo .env en la raiz del proyecto.
+    ///
+    /// El pool debe construirse una sola vez (en `main`) y compartirse
+    /// entre los workers de Actix mediante `web::Data`.
+    pub fn build_pool() -> Result<Pool, Box<dyn Error>> {
         dotenv().ok();
 
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-        Ok(SqliteConnection::establish(&database_url)?)
+        let manager = ConnectionManager::<SqliteConnection>::new(database_url);
+        Ok(R2d2Pool::builder().build(manager)?)
+    }
+
+    /// Aplica cualquier migración pendiente sobre la conexión dada. Se
+    /// invoca una sola vez en `main`, justo después de construir el pool,
+    /// para que el esquema quede al día sin intervención manual.
+    pub fn run_pending_migrations(conn: &mut SqliteConnection) -> Result<(), Box<dyn Error>> {
+        conn.run_pending_migrations(MIGRATIONS)
+            .map(|_| ())
+            .map_err(|e| e.into())
     }
 }
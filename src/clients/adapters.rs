@@ -1,19 +1,41 @@
-use crate::adapters::DieselConnector;
-use crate::entities::{Identifiable, Search, SoftDeletable};
+use crate::adapters::Connection;
+use crate::entities::{AggregationResult, Identifiable, Search, SoftDeletable};
+use chrono::Datelike;
+use crate::errors::{FieldError, RepositoryError};
+use crate::security::{Argon2Hasher, PasswordHasher};
 use crate::use_cases::{
-    Adder, Checker, Finder, LogicalDeleter, PermanentlyDeleter, Repository, Updater,
+    Adder, Analytics, Authenticator, Checker, Finder, LogicalDeleter, PermanentlyDeleter,
+    Repository, Updater,
 };
 
-use super::entities::{Client, ClientCriteria, NewClient};
+use super::entities::{Client, ClientCriteria, ClientGroupBy, ClientMetric, NewClient};
+use super::events::{client_event_sender, ClientEvent};
+use super::search::SearchIndex;
+use crate::adapters::Pool;
 use crate::schema::clients;
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use diesel::prelude::*;
-use diesel::{delete, insert_into, update};
+use diesel::sql_types::BigInt;
+use diesel::{delete, insert_into, sql_query, update};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
 pub struct ClientRepository;
 
+/// Convierte un error del índice de Tantivy en un `RepositoryError`
+/// reportable por los handlers de Actix.
+fn index_error(e: tantivy::TantivyError) -> RepositoryError {
+    RepositoryError::Validation(vec![FieldError::new("search_index", e.to_string())])
+}
+
+#[derive(QueryableByName)]
+struct LastInsertId {
+    #[diesel(sql_type = BigInt)]
+    id: i64,
+}
+
 impl ClientRepository {
-    pub fn item_is_valid(item: &Client) -> Result<(), String> {
+    pub fn item_is_valid(item: &Client) -> Result<(), RepositoryError> {
         Ok(())
     }
 
@@ -21,10 +43,93 @@ impl ClientRepository {
         15
     }
 
+    /// Reconstruye el índice de búsqueda de texto completo a partir de
+    /// todos los clientes presentes en la base de datos. Pensado para
+    /// llamarse una vez en el arranque del servidor.
+    pub fn rebuild_search_index(conn: &mut Connection) -> Result<(), RepositoryError> {
+        let all_clients = clients::table.load::<Client>(conn)?;
+        SearchIndex::global()
+            .rebuild_from(&all_clients)
+            .map_err(index_error)
+    }
+
+    /// Resuelve `criteria.query` contra el índice de Tantivy, conservando
+    /// el orden por score, y luego aplica el resto de `criteria`
+    /// (`active`/`username`/`birth_date`) como filtro exacto sobre los
+    /// clientes hidratados desde Diesel, igual que hace el camino de
+    /// coincidencia exacta en `search_by`, de modo que ambos criterios se
+    /// combinen con AND en lugar de que `query` los ignore.
+    fn search_by_fulltext(
+        conn: &mut Connection,
+        criteria: &ClientCriteria,
+        text_query: &str,
+        page_number: usize,
+    ) -> Result<Search<ClientCriteria>, RepositoryError> {
+        let page_size = Self::page_size();
+        let offset = (page_number - 1) * page_size;
+
+        let index = SearchIndex::global();
+        let total_hits = index.count(text_query).map_err(index_error)?;
+        if total_hits == 0 {
+            return Ok(Search::new(page_number, 0, criteria.clone(), "[]".to_string()));
+        }
+
+        // Trae todos los ids que coinciden por texto completo, en orden de
+        // score, para poder filtrarlos por el resto de `criteria` antes de
+        // paginar: paginar sobre el texto completo primero podría descartar
+        // páginas enteras de resultados que sí cumplen el resto de filtros.
+        let scored_ids = index.search(text_query, total_hits, 0).map_err(index_error)?;
+
+        let mut query = clients::table
+            .into_boxed()
+            .filter(clients::client_id.eq_any(scored_ids.clone()));
+        if let Some(active) = criteria.active {
+            query = query.filter(clients::active.eq(active));
+        }
+        if let Some(ref username) = criteria.username {
+            query = query.filter(clients::username.eq(username));
+        }
+        if let Some(birth_date) = criteria.birth_date {
+            query = query.filter(clients::birth_date.eq(birth_date));
+        }
+
+        let matched = query.load::<Client>(conn)?;
+        let mut by_id: std::collections::HashMap<i32, Client> = matched
+            .into_iter()
+            .filter_map(|client| client.client_id.map(|id| (id, client)))
+            .collect();
+
+        // Conserva el orden por score de Tantivy, descartando los ids que
+        // el filtro de Diesel eliminó.
+        let filtered_ids: Vec<i32> = scored_ids
+            .into_iter()
+            .filter(|id| by_id.contains_key(id))
+            .collect();
+
+        let total_count = filtered_ids.len() as i64;
+        let total_pages = Self::calculate_total_pages(total_count, page_size as i64);
+
+        let result: Vec<Client> = filtered_ids
+            .into_iter()
+            .skip(offset)
+            .take(page_size)
+            .filter_map(|id| by_id.remove(&id))
+            .collect();
+
+        Ok(Search::new(
+            page_number,
+            total_pages as usize,
+            criteria.clone(),
+            serde_json::to_string(&result).map_err(|e| {
+                RepositoryError::Validation(vec![FieldError::new("result", e.to_string())])
+            })?,
+        ))
+    }
+
     pub fn count_clients(
-        conn: &mut SqliteConnection, // Ajusta el tipo de conexión según tu base de datos
+        conn: &mut Connection,
         criteria: &ClientCriteria,
-    ) -> Result<i64, Box<dyn std::error::Error>> {
+    ) -> Result<i64, RepositoryError> {
         // Comienza la consulta
         let mut query = clients::table.into_boxed(); // `into_boxed` para permitir la construcción dinámica de la consulta
 
@@ -54,65 +159,79 @@ impl ClientRepository {
 }
 
 impl Adder<Client> for ClientRepository {
-    fn add(item: &Client) -> Result<(), Box<dyn std::error::Error>> {
-        let mut conn = DieselConnector::establish_connection()?;
-        insert_into(clients::table)
-            .values(item)
-            .execute(&mut conn)?;
+    fn add(conn: &mut Connection, item: &Client) -> Result<(), RepositoryError> {
+        insert_into(clients::table).values(item).execute(conn)?;
+
+        let last_id = sql_query("SELECT last_insert_rowid() as id")
+            .get_result::<LastInsertId>(conn)?
+            .id as i32;
+        let mut indexed = item.clone();
+        indexed.client_id = Some(last_id);
+        SearchIndex::global()
+            .index_client(&indexed)
+            .map_err(index_error)?;
+
+        // El `send` sólo falla cuando no hay suscriptores conectados, lo
+        // cual no es un error para quien está agregando el cliente.
+        let _ = client_event_sender().send(ClientEvent::Added(last_id));
+
         Ok(())
     }
 }
 
 impl PermanentlyDeleter<Client, Option<i32>> for ClientRepository {
-    fn permanently_delete(item: &Client) -> Result<(), Box<dyn std::error::Error>> {
-        if item.id().is_none() {
-            return Err("NoIDError: Item sould have an ID".into());
-        }
+    fn permanently_delete(conn: &mut Connection, item: &Client) -> Result<(), RepositoryError> {
+        let id = item.id().ok_or(RepositoryError::MissingId)?;
+
+        delete(clients::table.filter(clients::client_id.eq(id))).execute(conn)?;
+        SearchIndex::global()
+            .delete_client(id)
+            .map_err(index_error)?;
+
+        let _ = client_event_sender().send(ClientEvent::PermanentlyDeleted(id));
 
-        let mut conn = DieselConnector::establish_connection()?;
-        delete(clients::table.filter(clients::client_id.eq(item.id()))).execute(&mut conn)?;
         Ok(())
     }
 }
 
 impl Updater<Client, Option<i32>> for ClientRepository {
-    fn update(item: &Client) -> Result<(), Box<dyn std::error::Error>> {
+    fn update(conn: &mut Connection, item: &Client) -> Result<(), RepositoryError> {
         // Usamos una variable auxiliar porque no podemos pasar un
         // Option o algo así al filter
-        let id = item
-            .id()
-            .ok_or_else(|| "Item should have an ID".to_string())?;
-
-        // Establece la conexión
-        let mut conn = DieselConnector::establish_connection()?;
+        let id = item.id().ok_or(RepositoryError::MissingId)?;
 
         // Realiza la actualización utilizando el ID no nullable
         update(clients::table.filter(clients::client_id.eq(id)))
             .set((
                 clients::active.eq(item.active),
                 clients::username.eq(&item.username),
-                clients::pwd.eq(&item.pwd),
+                clients::phc_string.eq(&item.phc_string),
                 clients::birth_date.eq(item.birth_date),
             ))
-            .execute(&mut conn)?;
+            .execute(conn)?;
+
+        SearchIndex::global().index_client(item).map_err(index_error)?;
+
+        let _ = client_event_sender().send(ClientEvent::Updated(id));
 
         Ok(())
     }
 }
 
 impl LogicalDeleter<Client> for ClientRepository {
-    fn logically_delete(item: &Client) -> Result<(), Box<dyn std::error::Error>> {
-        let id = item
-            .id()
-            .ok_or_else(|| "Item should have an ID".to_string())?;
-
-        // Establece la conexión
-        let mut conn = DieselConnector::establish_connection()?;
+    fn logically_delete(conn: &mut Connection, item: &Client) -> Result<(), RepositoryError> {
+        let id = item.id().ok_or(RepositoryError::MissingId)?;
 
         // Realiza la actualización utilizando el ID no nullable
         diesel::update(clients::table.filter(clients::client_id.eq(id)))
             .set(clients::active.eq(false))
-            .execute(&mut conn)?;
+            .execute(conn)?;
+
+        SearchIndex::global()
+            .delete_client(id)
+            .map_err(index_error)?;
+
+        let _ = client_event_sender().send(ClientEvent::LogicallyDeleted(id));
 
         Ok(())
     }
@@ -120,9 +239,14 @@ impl LogicalDeleter<Client> for ClientRepository {
 
 impl Finder<Client, Option<i32>, ClientCriteria> for ClientRepository {
     fn search_by(
+        conn: &mut Connection,
         criteria: &ClientCriteria,
         page_number: usize,
-    ) -> Result<crate::entities::Search<ClientCriteria>, Box<dyn std::error::Error>> {
+    ) -> Result<crate::entities::Search<ClientCriteria>, RepositoryError> {
+        if let Some(ref text_query) = criteria.query {
+            return Self::search_by_fulltext(conn, criteria, text_query, page_number);
+        }
+
         // Comienza la consulta
         let mut query = clients::table.into_boxed(); // `into_boxed` para permitir la construcción dinámica de la consulta
 
@@ -143,30 +267,28 @@ impl Finder<Client, Option<i32>, ClientCriteria> for ClientRepository {
         query = query.limit(Self::page_size() as i64).offset(offset as i64);
 
         // Ejecuta la consulta
-        let mut conn = DieselConnector::establish_connection()?;
-        let result = query.load::<Client>(&mut conn)?;
+        let result = query.load::<Client>(conn)?;
 
-        let total_count = Self::count_clients(&mut conn, criteria)?;
+        let total_count = Self::count_clients(conn, criteria)?;
         let total_pages = Self::calculate_total_pages(total_count, Self::page_size() as i64);
 
         Ok(Search::new(
             page_number,
             total_pages as usize,
             criteria.clone(),
-            serde_json::to_string(&result)?,
+            serde_json::to_string(&result)
+                .map_err(|e| RepositoryError::Validation(vec![FieldError::new("result", e.to_string())]))?,
         ))
     }
 
-    fn search_by_id(id: usize) -> Result<Option<Client>, Box<dyn std::error::Error>> {
+    fn search_by_id(conn: &mut Connection, id: usize) -> Result<Option<Client>, RepositoryError> {
         let id = id as i32;
-        // Establish the connection to the database
-        let mut conn = DieselConnector::establish_connection()?;
 
         // Perform the query to find the client by ID
         let query = clients::table
             .into_boxed()
             .filter(clients::client_id.eq(id));
-        let result: Vec<Client> = query.load::<Client>(&mut conn)?;
+        let result: Vec<Client> = query.load::<Client>(conn)?;
 
         if let Some(client) = result.get(0) {
             Ok(Some(client.clone()))
@@ -178,24 +300,195 @@ impl Finder<Client, Option<i32>, ClientCriteria> for ClientRepository {
 
 impl Repository<Client, Option<i32>, ClientCriteria> for ClientRepository {}
 
-pub async fn add_client(persona: web::Json<NewClient>) -> impl Responder {
+impl Analytics<Client, ClientCriteria, ClientGroupBy, ClientMetric> for ClientRepository {
+    fn aggregate(
+        conn: &mut Connection,
+        criteria: &ClientCriteria,
+        group_by: ClientGroupBy,
+        metric: ClientMetric,
+    ) -> Result<AggregationResult, RepositoryError> {
+        // Reutiliza el mismo lenguaje de filtros exactos que `search_by`,
+        // sin el caso de texto completo: una agregación opera sobre el
+        // conjunto de clientes que cumplen los criterios, no sobre un score.
+        let mut query = clients::table.into_boxed();
+        if let Some(active) = criteria.active {
+            query = query.filter(clients::active.eq(active));
+        }
+        if let Some(ref username) = criteria.username {
+            query = query.filter(clients::username.eq(username));
+        }
+        if let Some(birth_date) = criteria.birth_date {
+            query = query.filter(clients::birth_date.eq(birth_date));
+        }
+
+        let rows = query.load::<Client>(conn)?;
+
+        let mut groups: BTreeMap<String, Vec<&Client>> = BTreeMap::new();
+        for client in &rows {
+            let key = match group_by {
+                ClientGroupBy::Active => {
+                    if client.active {
+                        "active".to_string()
+                    } else {
+                        "inactive".to_string()
+                    }
+                }
+                ClientGroupBy::BirthYear => client.birth_date.format("%Y").to_string(),
+            };
+            groups.entry(key).or_default().push(client);
+        }
+
+        let buckets = groups
+            .into_iter()
+            .map(|(key, items)| {
+                let value = match metric {
+                    ClientMetric::Count => items.len() as f64,
+                    ClientMetric::MinBirthDate => items
+                        .iter()
+                        .map(|c| c.birth_date.num_days_from_ce())
+                        .min()
+                        .unwrap_or(0) as f64,
+                    ClientMetric::MaxBirthDate => items
+                        .iter()
+                        .map(|c| c.birth_date.num_days_from_ce())
+                        .max()
+                        .unwrap_or(0) as f64,
+                    ClientMetric::AvgBirthDate => {
+                        let days: Vec<i32> =
+                            items.iter().map(|c| c.birth_date.num_days_from_ce()).collect();
+                        if days.is_empty() {
+                            0.0
+                        } else {
+                            days.iter().map(|&d| d as i64).sum::<i64>() as f64
+                                / days.len() as f64
+                        }
+                    }
+                };
+                (key, value)
+            })
+            .collect();
+
+        Ok(AggregationResult { buckets })
+    }
+}
+
+impl PasswordHasher for ClientRepository {
+    fn hash_password(password: &str) -> Result<String, Box<dyn std::error::Error>> {
+        Argon2Hasher::hash_password(password)
+    }
+
+    fn verify_password(
+        password: &str,
+        phc_string: &str,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        Argon2Hasher::verify_password(password, phc_string)
+    }
+}
+
+impl Authenticator<Client> for ClientRepository {
+    fn authenticate(
+        conn: &mut Connection,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<Client>, RepositoryError> {
+        let result: Vec<Client> = clients::table
+            .filter(clients::username.eq(username))
+            .load::<Client>(conn)?;
+
+        let Some(client) = result.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let verified = Self::verify_password(password, &client.phc_string).map_err(|e| {
+            RepositoryError::Validation(vec![FieldError::new("pwd", e.to_string())])
+        })?;
+
+        if verified {
+            Ok(Some(client))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+pub async fn add_client(
+    pool: web::Data<Pool>,
+    persona: web::Json<NewClient>,
+) -> Result<HttpResponse, RepositoryError> {
+    let phc_string = ClientRepository::hash_password(&persona.pwd)
+        .map_err(|e| RepositoryError::Validation(vec![FieldError::new("pwd", e.to_string())]))?;
+
     let persona = Client {
         client_id: None,
         active: true,
         username: persona.username.to_string(),
-        pwd: persona.pwd.to_string(),
+        phc_string,
         birth_date: persona.birth_date,
     };
 
-    if let Err(e) = ClientRepository::item_is_valid(&persona) {
-        return HttpResponse::BadRequest().json(serde_json::json!({"error": e.to_string() }));
-    }
+    ClientRepository::item_is_valid(&persona)?;
 
-    match ClientRepository::add(&persona) {
-        Ok(_) => HttpResponse::Created().json(serde_json::json!({"success": true})),
-        Err(e) => {
-            println!("Error {}", e);
-            HttpResponse::BadRequest().json(serde_json::json!({"error": e.to_string() }))
-        }
+    let mut conn = pool.get()?;
+
+    ClientRepository::add(&mut conn, &persona)?;
+    Ok(HttpResponse::Created().json(serde_json::json!({"success": true})))
+}
+
+/// Credenciales recibidas en `/clients/login`.
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub pwd: String,
+}
+
+pub async fn login_client(
+    pool: web::Data<Pool>,
+    credentials: web::Json<LoginRequest>,
+) -> Result<HttpResponse, RepositoryError> {
+    let mut conn = pool.get()?;
+
+    match ClientRepository::authenticate(&mut conn, &credentials.username, &credentials.pwd)? {
+        Some(client) => Ok(HttpResponse::Ok().json(serde_json::json!({"client_id": client.id()}))),
+        None => Ok(HttpResponse::Unauthorized()
+            .json(serde_json::json!({"error": "invalid credentials"}))),
     }
 }
+
+/// Parámetros de query string aceptados por `/clients/analytics`: los
+/// mismos campos de `ClientCriteria` usados para filtrar en `search_by`,
+/// inlineados en lugar de anidados, más el selector de agrupación y la
+/// métrica a calcular por grupo.
+///
+/// `#[serde(flatten)]` sobre un `ClientCriteria` anidado no funciona con
+/// `web::Query`, que deserializa vía `serde_urlencoded`: este no soporta
+/// structs aplanados, así que cada campo se declara directamente aquí.
+#[derive(Debug, Deserialize)]
+pub struct AnalyticsQuery {
+    pub client_id: Option<i32>,
+    pub active: Option<bool>,
+    pub username: Option<String>,
+    pub birth_date: Option<chrono::NaiveDate>,
+    pub query: Option<String>,
+    pub group_by: ClientGroupBy,
+    pub metric: ClientMetric,
+}
+
+pub async fn client_analytics(
+    pool: web::Data<Pool>,
+    query: web::Query<AnalyticsQuery>,
+) -> Result<HttpResponse, RepositoryError> {
+    let mut conn = pool.get()?;
+
+    let criteria = ClientCriteria {
+        client_id: query.client_id,
+        active: query.active,
+        username: query.username.clone(),
+        birth_date: query.birth_date,
+        query: query.query.clone(),
+    };
+
+    let result =
+        ClientRepository::aggregate(&mut conn, &criteria, query.group_by, query.metric)?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
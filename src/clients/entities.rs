@@ -13,7 +13,7 @@ pub struct Client {
     pub client_id: Option<i32>, // Cambiado a client_id para coincidir con el nombre del campo en el esquema
     pub active: bool,
     pub username: String,
-    pub pwd: String,
+    pub phc_string: String, // Hash de la contraseña en formato PHC, nunca la contraseña en texto plano
     pub birth_date: NaiveDate, // Cambiado a birth_date para coincidir con el nombre del campo en el esquema
 }
 
@@ -24,8 +24,12 @@ pub struct ClientCriteria {
     pub client_id: Option<i32>, // Cambiado a client_id para coincidir con el nombre del campo en el esquema
     pub active: Option<bool>,
     pub username: Option<String>,
-    pub pwd: Option<String>,
     pub birth_date: Option<NaiveDate>, // Cambiado a birth_date para coincidir con el nombre del campo en el esquema
+    /// Texto libre para búsqueda por palabras sobre `username` vía el
+    /// índice de Tantivy. Si está presente, `Finder::search_by` usa el
+    /// backend de texto completo y aplica además `active`/`username`/
+    /// `birth_date` como filtros exactos sobre los resultados.
+    pub query: Option<String>,
 }
 
 impl Identifiable<Option<i32>> for Client {
@@ -49,4 +53,30 @@ pub struct NewClient {
     pub username: String,
     pub pwd: String,
     pub birth_date: NaiveDate, // Cambiado a birth_date para coincidir con el nombre del campo en el esquema
+}
+
+/// Campo por el cual `/clients/analytics` agrupa los resultados.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientGroupBy {
+    /// Desglosa entre clientes activos e inactivos (eliminados lógicamente).
+    Active,
+    /// Agrupa por año de nacimiento.
+    BirthYear,
+}
+
+/// Métrica calculada por cada grupo en `/clients/analytics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientMetric {
+    /// Número de clientes en el grupo.
+    Count,
+    /// Fecha de nacimiento más antigua del grupo, como día ordinal
+    /// proléptico gregoriano (`NaiveDate::num_days_from_ce`).
+    MinBirthDate,
+    /// Fecha de nacimiento más reciente del grupo, en el mismo formato.
+    MaxBirthDate,
+    /// Promedio de las fechas de nacimiento del grupo, en el mismo
+    /// formato de día ordinal proléptico gregoriano.
+    AvgBirthDate,
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+use std::sync::OnceLock;
+
+use tokio::sync::broadcast;
+
+use crate::entities::RepoEvent;
+
+/// Cuántos eventos sin consumir se retienen por suscriptor antes de que el
+/// canal empiece a descartar los más antiguos.
+const CHANNEL_CAPACITY: usize = 128;
+
+/// Evento de `ClientRepository`, identificando el cliente afectado por su
+/// `client_id`.
+pub type ClientEvent = RepoEvent<i32>;
+
+static CHANNEL: OnceLock<broadcast::Sender<ClientEvent>> = OnceLock::new();
+
+/// Devuelve el `Sender` global del canal de eventos de clientes, creándolo
+/// la primera vez que se solicita. Los handlers de Actix registran un
+/// clon de este `Sender` en `web::Data` para poder suscribirse desde
+/// `/clients/events`; `ClientRepository` publica en él directamente tras
+/// cada mutación confirmada.
+pub fn client_event_sender() -> &'static broadcast::Sender<ClientEvent> {
+    CHANNEL.get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+}
@@ -0,0 +1,5 @@
+pub mod adapters;
+pub mod entities;
+pub mod events;
+pub mod search;
+pub mod ws;
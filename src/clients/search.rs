@@ -0,0 +1,135 @@
+use std::sync::{Mutex, OnceLock};
+
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, STORED, TEXT};
+use tantivy::{doc, Index, IndexWriter, Term};
+
+use super::entities::Client;
+
+static INDEX: OnceLock<SearchIndex> = OnceLock::new();
+
+/// Índice de texto completo (Tantivy) sobre los campos buscables de
+/// `Client`, mantenido en sincronía con la base de datos desde
+/// `ClientRepository`. Vive en memoria durante la vida del proceso; se
+/// puede reconstruir por completo desde la base de datos con
+/// `rebuild_from` en un arranque en frío.
+///
+/// Tantivy sólo permite un `IndexWriter` vivo a la vez, así que se
+/// mantiene uno único detrás de un `Mutex` en vez de abrir uno por
+/// llamada: con varios workers de Actix mutando el índice a la vez,
+/// abrir/cerrar un writer en cada `index_client`/`delete_client` competía
+/// por el lock interno de Tantivy y producía fallas espurias.
+pub struct SearchIndex {
+    index: Index,
+    writer: Mutex<IndexWriter>,
+    client_id_field: Field,
+    username_field: Field,
+}
+
+impl SearchIndex {
+    /// Devuelve la instancia global del índice, creándola la primera vez
+    /// que se solicita.
+    pub fn global() -> &'static SearchIndex {
+        INDEX.get_or_init(|| SearchIndex::new().expect("failed to build the Tantivy index"))
+    }
+
+    fn new() -> tantivy::Result<Self> {
+        let mut schema_builder = Schema::builder();
+        let client_id_field = schema_builder.add_i64_field("client_id", STORED);
+        let username_field = schema_builder.add_text_field("username", TEXT | STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let writer = index.writer(50_000_000)?;
+
+        Ok(Self {
+            index,
+            writer: Mutex::new(writer),
+            client_id_field,
+            username_field,
+        })
+    }
+
+    /// Inserta o reemplaza el documento correspondiente a `client` en el
+    /// índice. `client.client_id` debe estar presente.
+    pub fn index_client(&self, client: &Client) -> tantivy::Result<()> {
+        let Some(client_id) = client.client_id else {
+            return Ok(());
+        };
+
+        let mut writer = self.writer.lock().expect("tantivy writer lock poisoned");
+        writer.delete_term(Term::from_field_i64(self.client_id_field, client_id as i64));
+        writer.add_document(doc!(
+            self.client_id_field => client_id as i64,
+            self.username_field => client.username.clone(),
+        ))?;
+        writer.commit()?;
+        Ok(())
+    }
+
+    /// Elimina el documento asociado a `client_id`, si existe.
+    pub fn delete_client(&self, client_id: i32) -> tantivy::Result<()> {
+        let mut writer = self.writer.lock().expect("tantivy writer lock poisoned");
+        writer.delete_term(Term::from_field_i64(self.client_id_field, client_id as i64));
+        writer.commit()?;
+        Ok(())
+    }
+
+    /// Vacía el índice y lo reconstruye a partir de la lista de clientes
+    /// dada, pensado para arranques en frío donde el índice en memoria se
+    /// perdió entre reinicios.
+    pub fn rebuild_from(&self, clients: &[Client]) -> tantivy::Result<()> {
+        {
+            let mut writer = self.writer.lock().expect("tantivy writer lock poisoned");
+            writer.delete_all_documents()?;
+            writer.commit()?;
+        }
+
+        for client in clients {
+            self.index_client(client)?;
+        }
+
+        Ok(())
+    }
+
+    /// Ejecuta `query` contra el campo `username` y devuelve los
+    /// `client_id` que coinciden, ordenados por score descendente,
+    /// aplicando `limit`/`offset` para paginación.
+    pub fn search(&self, query: &str, limit: usize, offset: usize) -> tantivy::Result<Vec<i32>> {
+        // `TopDocs::with_limit` entra en pánico con un límite de 0, así que
+        // una búsqueda sin resultados pedidos se resuelve sin tocarlo.
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let reader = self.index.reader()?;
+        let searcher = reader.searcher();
+        let query_parser = QueryParser::for_index(&self.index, vec![self.username_field]);
+        let parsed_query = query_parser.parse_query(query)?;
+
+        let top_docs = searcher.search(&parsed_query, &TopDocs::with_limit(limit + offset))?;
+
+        let mut ids = Vec::with_capacity(top_docs.len().saturating_sub(offset));
+        for (_score, doc_address) in top_docs.into_iter().skip(offset) {
+            let retrieved = searcher.doc::<tantivy::TantivyDocument>(doc_address)?;
+            if let Some(id) = retrieved
+                .get_first(self.client_id_field)
+                .and_then(|v| v.as_i64())
+            {
+                ids.push(id as i32);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Cuenta el total de documentos que coinciden con `query`,
+    /// independientemente de la paginación, para poblar `total_pages`.
+    pub fn count(&self, query: &str) -> tantivy::Result<usize> {
+        let reader = self.index.reader()?;
+        let searcher = reader.searcher();
+        let query_parser = QueryParser::for_index(&self.index, vec![self.username_field]);
+        let parsed_query = query_parser.parse_query(query)?;
+        searcher.search(&parsed_query, &Count)
+    }
+}
@@ -0,0 +1,110 @@
+use actix::{Actor, ActorContext, AsyncContext, Handler, Message, StreamHandler};
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use actix_web_actors::ws;
+use tokio::sync::broadcast;
+
+use super::entities::ClientCriteria;
+use super::events::ClientEvent;
+
+/// Mensaje interno usado para reenviar un `ClientEvent` recibido del canal
+/// de broadcast hacia el actor del WebSocket.
+#[derive(Message)]
+#[rtype(result = "()")]
+struct Forward(ClientEvent);
+
+/// Actor WebSocket que reenvía los `ClientEvent` emitidos por
+/// `ClientRepository` a un único cliente conectado, opcionalmente
+/// filtrados por `client_id` cuando el suscriptor sólo quiere observar un
+/// cliente en particular.
+pub struct ClientEventsWs {
+    events: broadcast::Sender<ClientEvent>,
+    filter_client_id: Option<i32>,
+}
+
+impl ClientEventsWs {
+    fn matches(&self, event: &ClientEvent) -> bool {
+        match self.filter_client_id {
+            None => true,
+            Some(wanted) => event_id(event) == wanted,
+        }
+    }
+}
+
+fn event_id(event: &ClientEvent) -> i32 {
+    match event {
+        ClientEvent::Added(id)
+        | ClientEvent::Updated(id)
+        | ClientEvent::LogicallyDeleted(id)
+        | ClientEvent::PermanentlyDeleted(id) => *id,
+    }
+}
+
+impl Actor for ClientEventsWs {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        let mut receiver = self.events.subscribe();
+        let addr = ctx.address();
+
+        actix::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if addr.try_send(Forward(event)).is_err() {
+                            break;
+                        }
+                    }
+                    // El suscriptor se quedó atrás del buffer de 128 slots;
+                    // se pierden esos eventos pero el socket sigue vivo.
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+impl Handler<Forward> for ClientEventsWs {
+    type Result = ();
+
+    fn handle(&mut self, msg: Forward, ctx: &mut Self::Context) {
+        if !self.matches(&msg.0) {
+            return;
+        }
+
+        if let Ok(json) = serde_json::to_string(&msg.0) {
+            ctx.text(json);
+        }
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ClientEventsWs {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Handler de `/clients/events`. Acepta un `ClientCriteria` por query
+/// string como filtro de suscripción; por ahora sólo se honra
+/// `client_id`, dejando el resto de campos disponibles para futuros
+/// filtros más finos.
+pub async fn client_events_ws(
+    req: HttpRequest,
+    stream: web::Payload,
+    events: web::Data<broadcast::Sender<ClientEvent>>,
+    filter: web::Query<ClientCriteria>,
+) -> Result<HttpResponse, Error> {
+    let actor = ClientEventsWs {
+        events: events.get_ref().clone(),
+        filter_client_id: filter.client_id,
+    };
+
+    ws::start(actor, &req, stream)
+}
@@ -186,3 +186,24 @@ impl<Criteria> Search<Criteria> {
         }
     }
 }
+
+/// `RepoEvent` representa una mutación que ocurrió sobre un `Repository`,
+/// identificando únicamente el id del item afectado. Se difunde por un
+/// canal de broadcast para que los suscriptores puedan reaccionar a los
+/// cambios en lugar de sondear `Finder::search_by` repetidamente.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum RepoEvent<IdType> {
+    Added(IdType),
+    Updated(IdType),
+    LogicallyDeleted(IdType),
+    PermanentlyDeleted(IdType),
+}
+
+/// `AggregationResult` es el resultado de una operación de `Analytics`:
+/// una lista de pares (etiqueta de grupo, valor numérico de la métrica
+/// calculada para ese grupo).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregationResult {
+    pub buckets: Vec<(String, f64)>,
+}
@@ -0,0 +1,108 @@
+use actix_web::{HttpResponse, ResponseError};
+use std::fmt;
+
+/// Describe por qué un campo concreto no superó la validación.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Error tipado compartido por `use_cases` y `ClientRepository`, de modo que
+/// los handlers de Actix puedan distinguir "no encontrado" de "entrada
+/// inválida" de "falla de base de datos" en lugar de recibir un
+/// `Box<dyn Error>` opaco.
+#[derive(Debug)]
+pub enum RepositoryError {
+    /// No se encontró ningún registro que cumpla con el criterio solicitado.
+    NotFound,
+    /// Se intentó operar sobre un item que no trae un identificador.
+    MissingId,
+    /// Uno o más campos del item no cumplen las reglas de validación.
+    Validation(Vec<FieldError>),
+    /// La operación viola una restricción de unicidad u otro invariante.
+    Conflict,
+    /// Falla no anticipada proveniente de Diesel.
+    Database(diesel::result::Error),
+    /// No se pudo obtener una conexión del pool (agotado o DB inalcanzable).
+    Pool(diesel::r2d2::Error),
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepositoryError::NotFound => write!(f, "the requested item was not found"),
+            RepositoryError::MissingId => write!(f, "item should have an ID"),
+            RepositoryError::Validation(errors) => {
+                write!(f, "validation failed: ")?;
+                for (i, e) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", e.field, e.message)?;
+                }
+                Ok(())
+            }
+            RepositoryError::Conflict => write!(f, "the operation conflicts with existing data"),
+            RepositoryError::Database(e) => write!(f, "database error: {}", e),
+            RepositoryError::Pool(e) => write!(f, "connection pool error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RepositoryError::Database(e) => Some(e),
+            RepositoryError::Pool(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<diesel::result::Error> for RepositoryError {
+    fn from(e: diesel::result::Error) -> Self {
+        match e {
+            diesel::result::Error::NotFound => RepositoryError::NotFound,
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => RepositoryError::Conflict,
+            other => RepositoryError::Database(other),
+        }
+    }
+}
+
+impl From<diesel::r2d2::Error> for RepositoryError {
+    fn from(e: diesel::r2d2::Error) -> Self {
+        RepositoryError::Pool(e)
+    }
+}
+
+impl ResponseError for RepositoryError {
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            RepositoryError::NotFound => {
+                HttpResponse::NotFound().json(serde_json::json!({"error": self.to_string()}))
+            }
+            RepositoryError::MissingId | RepositoryError::Validation(_) => HttpResponse::BadRequest()
+                .json(serde_json::json!({"error": self.to_string()})),
+            RepositoryError::Conflict => {
+                HttpResponse::Conflict().json(serde_json::json!({"error": self.to_string()}))
+            }
+            RepositoryError::Database(_) | RepositoryError::Pool(_) => {
+                HttpResponse::InternalServerError()
+                    .json(serde_json::json!({"error": self.to_string()}))
+            }
+        }
+    }
+}
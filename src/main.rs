@@ -1,11 +1,16 @@
 mod adapters;
 mod clients;
 mod entities;
+mod errors;
 mod schema;
+mod security;
 mod use_cases;
 
 use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
-use clients::adapters::add_client;
+use adapters::DieselConnector;
+use clients::adapters::{add_client, client_analytics, login_client, ClientRepository};
+use clients::events::client_event_sender;
+use clients::ws::client_events_ws;
 
 #[get("/")]
 async fn hello() -> impl Responder {
@@ -22,12 +27,29 @@ async fn manual_hello() -> impl Responder {
 }
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| {
+    let pool = DieselConnector::build_pool().expect("failed to build the connection pool");
+
+    {
+        let mut conn = pool
+            .get()
+            .expect("failed to get a connection to run migrations");
+        DieselConnector::run_pending_migrations(&mut conn)
+            .expect("failed to run pending migrations");
+        ClientRepository::rebuild_search_index(&mut conn)
+            .expect("failed to rebuild the client search index");
+    }
+
+    HttpServer::new(move || {
         App::new()
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(client_event_sender().clone()))
             .service(hello)
             .service(echo)
             .route("/hey", web::get().to(manual_hello))
             .route("/clients/add", web::post().to(add_client))
+            .route("/clients/login", web::post().to(login_client))
+            .route("/clients/events", web::get().to(client_events_ws))
+            .route("/clients/analytics", web::get().to(client_analytics))
     })
     .bind(("127.0.0.1", 8080))?
     .run()
@@ -0,0 +1,36 @@
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use std::error::Error;
+
+/// `PasswordHasher` aísla el algoritmo usado para convertir una contraseña
+/// en texto plano en un hash almacenable (formato PHC) y para verificarla
+/// después, de modo que el resto del código nunca manipule contraseñas
+/// crudas más allá de este punto.
+pub trait PasswordHasher {
+    fn hash_password(password: &str) -> Result<String, Box<dyn Error>>;
+    fn verify_password(password: &str, phc_string: &str) -> Result<bool, Box<dyn Error>>;
+}
+
+/// Implementación de `PasswordHasher` basada en argon2id.
+pub struct Argon2Hasher;
+
+impl PasswordHasher for Argon2Hasher {
+    fn hash_password(password: &str) -> Result<String, Box<dyn Error>> {
+        let salt = SaltString::generate(&mut OsRng);
+        let phc_string = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| e.to_string())?
+            .to_string();
+
+        Ok(phc_string)
+    }
+
+    fn verify_password(password: &str, phc_string: &str) -> Result<bool, Box<dyn Error>> {
+        let parsed_hash = PasswordHash::new(phc_string).map_err(|e| e.to_string())?;
+
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &parsed_hash)
+            .is_ok())
+    }
+}
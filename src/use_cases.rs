@@ -1,32 +1,33 @@
 use serde::Serialize;
-use std::error::Error;
 
-use crate::entities::{Identifiable, Search, SoftDeletable};
+use crate::adapters::Connection;
+use crate::entities::{AggregationResult, Identifiable, Search, SoftDeletable};
+use crate::errors::RepositoryError;
 pub trait Adder<Item>
 where
     Item: Clone + PartialEq + Serialize,
 {
-    fn add(item: &Item) -> Result<(), Box<dyn Error>>;
+    fn add(conn: &mut Connection, item: &Item) -> Result<(), RepositoryError>;
 }
 pub trait Updater<Item, IdType>
 where
     IdType: Serialize,
     Item: Clone + PartialEq + Serialize + Identifiable<IdType>,
 {
-    fn update(item: &Item) -> Result<(), Box<dyn Error>>;
+    fn update(conn: &mut Connection, item: &Item) -> Result<(), RepositoryError>;
 }
 pub trait LogicalDeleter<Item>
 where
     Item: Clone + PartialEq + Serialize + SoftDeletable,
 {
-    fn logically_delete(item: &Item) -> Result<(), Box<dyn Error>>;
+    fn logically_delete(conn: &mut Connection, item: &Item) -> Result<(), RepositoryError>;
 }
 pub trait PermanentlyDeleter<Item, IdType>
 where
     IdType: Serialize,
     Item: Clone + PartialEq + Serialize + Identifiable<IdType>,
 {
-    fn permanently_delete(item: &Item) -> Result<(), Box<dyn Error>>;
+    fn permanently_delete(conn: &mut Connection, item: &Item) -> Result<(), RepositoryError>;
 }
 pub trait Finder<Model, IdType, Criteria>
 where
@@ -34,11 +35,12 @@ where
     IdType: Serialize,
     Model: PartialEq + Clone + Identifiable<IdType>,
 {
-    fn search_by_id(id: usize) -> Result<Option<Model>, Box<dyn Error>>;
+    fn search_by_id(conn: &mut Connection, id: usize) -> Result<Option<Model>, RepositoryError>;
     fn search_by(
+        conn: &mut Connection,
         criteria: &Criteria,
         page_number: usize,
-    ) -> Result<Search<Criteria>, Box<dyn Error>>;
+    ) -> Result<Search<Criteria>, RepositoryError>;
 }
 pub trait Repository<Item, IdType, Criteria>:
     Adder<Item>
@@ -54,5 +56,33 @@ where
 }
 
 pub trait Checker<Item, Repository> {
-    fn item_is_valid(item: &Item) -> Result<(), Box<dyn Error>>;
+    fn item_is_valid(item: &Item) -> Result<(), RepositoryError>;
+}
+
+/// `Authenticator` carga un `Item` por su nombre de usuario y verifica la
+/// contraseña recibida contra el hash almacenado, devolviendo el item sólo
+/// si la verificación tiene éxito.
+pub trait Authenticator<Item>
+where
+    Item: Clone + PartialEq + Serialize,
+{
+    fn authenticate(
+        conn: &mut Connection,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<Item>, RepositoryError>;
+}
+
+/// `Analytics` construye agregaciones (conteos, min/max, desgloses) sobre
+/// un `Model` filtrado por `Criteria`, agrupando por `GroupBy` y
+/// calculando `Metric` por grupo. Reutiliza el mismo lenguaje de filtros
+/// que ya usa `Finder::search_by` en lugar de introducir uno nuevo para
+/// reportes.
+pub trait Analytics<Model, Criteria, GroupBy, Metric> {
+    fn aggregate(
+        conn: &mut Connection,
+        criteria: &Criteria,
+        group_by: GroupBy,
+        metric: Metric,
+    ) -> Result<AggregationResult, RepositoryError>;
 }